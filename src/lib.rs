@@ -1,14 +1,18 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use log::*;
 use screeps::{
-    find, game, prelude::*, Creep, ObjectId, Part, ResourceType, ReturnCode, RoomObjectProperties,
-    Source, StructureController, StructureObject,
+    find, game, memory, prelude::*, ConstructionSite, Creep, ObjectId, Part, Resource,
+    ResourceType, ReturnCode, RoomObjectProperties, Source, StructureContainer,
+    StructureController, StructureExtension, StructureObject, StructurePowerSpawn,
+    StructureSpawn, StructureStorage, Transferable, Withdrawable,
 };
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 mod logging;
+mod power_creep;
 
 // add wasm_bindgen to any function you would like to expose for call from js
 #[wasm_bindgen]
@@ -16,19 +20,115 @@ pub fn setup() {
     logging::setup_logging(logging::Info);
 }
 
+// a creep's job within the colony; determines which `find_work_target_*`
+// function picks its target once it's full (see `find_work_target`) and is
+// kept stable for the creep's lifetime by encoding it in the creep's name
+// (see `role_from_creep_name`)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+enum Role {
+    Harvester,
+    Builder,
+    Upgrader,
+    Hauler,
+}
+
+impl Role {
+    fn name(self) -> &'static str {
+        match self {
+            Role::Harvester => "harvester",
+            Role::Builder => "builder",
+            Role::Upgrader => "upgrader",
+            Role::Hauler => "hauler",
+        }
+    }
+}
+
+const ROLES: [Role; 4] = [Role::Harvester, Role::Builder, Role::Upgrader, Role::Hauler];
+
+// desired population and body blueprint per role; `body_segment` is repeated
+// as many times as the room's energy capacity allows (see `body_for_energy`)
+// so the same config produces small early-game creeps and large late-game ones
+struct RoleConfig {
+    role: Role,
+    count: u32,
+    body_segment: &'static [Part],
+}
+
+const ROLE_CONFIGS: [RoleConfig; 4] = [
+    RoleConfig {
+        role: Role::Harvester,
+        count: 2,
+        body_segment: &[Part::Move, Part::Work, Part::Carry],
+    },
+    RoleConfig {
+        role: Role::Hauler,
+        count: 2,
+        body_segment: &[Part::Move, Part::Carry],
+    },
+    RoleConfig {
+        role: Role::Builder,
+        count: 2,
+        body_segment: &[Part::Move, Part::Work, Part::Carry],
+    },
+    RoleConfig {
+        role: Role::Upgrader,
+        count: 2,
+        body_segment: &[Part::Move, Part::Work, Part::Carry],
+    },
+];
+
+// screeps caps creep bodies at 50 parts regardless of available energy
+const MAX_BODY_PARTS: u32 = 50;
+
+// the structures a hauler is willing to top up; kept separate from
+// `CreepTarget::Transfer` since spawns and extensions don't share a common
+// `ObjectId`-compatible type in screeps-game-api
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum TransferTarget {
+    Spawn(ObjectId<StructureSpawn>),
+    Extension(ObjectId<StructureExtension>),
+    Storage(ObjectId<StructureStorage>),
+}
+
+// the structures a hauler can draw energy back out of; kept separate from
+// `CreepTarget::Withdraw` for the same reason as `TransferTarget`
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum WithdrawTarget {
+    Container(ObjectId<StructureContainer>),
+    Storage(ObjectId<StructureStorage>),
+}
+
 // this enum will represent a creep's lock on a specific target object, storing a js reference to the object id so that we can grab a fresh reference to the object each successive tick, since screeps game objects become 'stale' and shouldn't be used beyond the tick they were fetched
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 enum CreepTarget {
     Upgrade(ObjectId<StructureController>),
     Harvest(ObjectId<Source>),
+    Build(ObjectId<ConstructionSite>),
+    Transfer(TransferTarget),
+    Withdraw(WithdrawTarget),
+    Pickup(ObjectId<Resource>),
+}
+
+// explicit phase of a creep's fill/work cycle; `next_state` only flips this
+// once the store is fully empty or fully full (hysteresis), so a creep keeps
+// the same kind of target across the whole phase instead of re-picking one
+// every tick it happens to dip below/above some threshold mid-phase
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum State {
+    Collecting,
+    Working,
 }
 
+#[derive(Serialize, Deserialize)]
 struct CreepState {
+    role: Role,
+    state: State,
     target: Option<CreepTarget>,
 }
 
-// this is one way to persist data between ticks within Rust's memory, as opposed to
-// keeping state in memory on game objects - but will be lost on global resets!
+// this is one way to persist data between ticks within Rust's memory; it's lost
+// on global resets, but `run_creeps` mirrors every state into `Memory.creeps` (see
+// `save_creep_state_to_memory`) and rehydrates from there when it finds this empty
 thread_local! {
     static CREEP_STATES: RefCell<HashMap<String, CreepState>> = RefCell::new(HashMap::new());
 }
@@ -37,32 +137,142 @@ thread_local! {
 #[wasm_bindgen(js_name = loop)]
 pub fn game_loop() {
     debug!("loop starting! CPU: {}", game::cpu::get_used());
+    cleanup_memory();
     run_creeps();
     debug!("running spawns");
     spawn_creeps();
+    debug!("running power spawns");
+    run_power_spawns();
+    debug!("running power creeps");
+    power_creep::run_power_creeps();
     info!("done! cpu: {}", game::cpu::get_used())
 }
 
+// key under each creep's own `Memory.creeps[name]` dict where its serialized
+// `CreepState` lives
+const CREEP_STATE_MEMORY_KEY: &str = "state";
+
+fn save_creep_state_to_memory(name: &str, creep_state: &CreepState) {
+    let creeps_memory = match memory::ROOT.dict_or_create("creeps") {
+        Ok(dict) => dict,
+        Err(e) => {
+            warn!("couldn't get creeps dictionary from memory: {:?}", e);
+            return;
+        }
+    };
+
+    let creep_memory = match creeps_memory.dict_or_create(name) {
+        Ok(dict) => dict,
+        Err(e) => {
+            warn!("couldn't get memory for creep {}: {:?}", name, e);
+            return;
+        }
+    };
+
+    match serde_json::to_string(creep_state) {
+        Ok(json) => creep_memory.set(CREEP_STATE_MEMORY_KEY, json),
+        Err(e) => warn!("couldn't serialize state for creep {}: {:?}", name, e),
+    }
+}
+
+// rehydrates `CREEP_STATES` from `Memory.creeps` after a global reset; creeps
+// with no stored state (or a stale/corrupt one) just re-derive their role from
+// their name and pick a fresh target on their next tick
+fn load_creep_states_from_memory() -> HashMap<String, CreepState> {
+    let mut creep_states = HashMap::new();
+
+    let creeps_memory = match memory::ROOT.dict("creeps") {
+        Some(dict) => dict,
+        None => return creep_states,
+    };
+
+    for name in creeps_memory.keys() {
+        let creep_memory = match creeps_memory.dict(&name) {
+            Some(dict) => dict,
+            None => continue,
+        };
+
+        let json = match creep_memory.get::<String>(CREEP_STATE_MEMORY_KEY) {
+            Ok(Some(json)) => json,
+            _ => continue,
+        };
+
+        match serde_json::from_str(&json) {
+            Ok(creep_state) => {
+                creep_states.insert(name, creep_state);
+            }
+            Err(e) => warn!("couldn't deserialize state for creep {}: {:?}", name, e),
+        }
+    }
+
+    creep_states
+}
+
+// `Memory.creeps[name]` is never removed by the engine once a creep dies, so
+// without this cleanup it grows forever across a colony's lifetime
+fn cleanup_memory() {
+    let alive_creeps: HashSet<String> = game::creeps().keys().collect();
+
+    let creeps_memory = match memory::ROOT.dict("creeps") {
+        Some(dict) => dict,
+        None => {
+            warn!("couldn't get creeps dictionary from memory");
+            return;
+        }
+    };
+
+    for name in creeps_memory.keys() {
+        if !alive_creeps.contains(&name) {
+            debug!("cleaning up creep memory for dead creep {}", name);
+            creeps_memory.del(&name);
+        }
+    }
+}
+
+// recovers a creep's role from the prefix spawn_creeps gave its name; unrecognised
+// or pre-existing names fall back to the generalist harvester role
+fn role_from_creep_name(name: &str) -> Role {
+    ROLES
+        .into_iter()
+        .find(|role| name.starts_with(role.name()))
+        .unwrap_or(Role::Harvester)
+}
+
 fn run_creeps() {
     // mutably borrow the creep_targets refcell, which is holding our creep target locks
     // in the wasm heap
     CREEP_STATES.with(|creep_states_refcell| {
         let mut creep_states = creep_states_refcell.borrow_mut();
+
+        // empty only happens right after a global reset; repopulate from Memory
+        // instead of letting every creep forget its role and target
+        if creep_states.is_empty() {
+            *creep_states = load_creep_states_from_memory();
+        }
+
+        // `game::creeps()` only ever hands back living creeps, so anything
+        // left over here belongs to a dead one; drop it now or it keeps
+        // counting toward its role's quota in `spawn_creeps` forever
+        let alive_creeps: HashSet<String> = game::creeps().keys().collect();
+        creep_states.retain(|name, _| alive_creeps.contains(name));
+
         debug!("running creeps");
         // same type conversion (and type assumption) as the spawn loop
         for creep in game::creeps().values() {
             let creep_name = creep.name();
             debug!("running creep {}", creep_name);
 
-            match creep_states.remove(&creep_name) {
-                Some(creep_state) => {
-                    let creep_state = run_creep(&creep, creep_state);
-                    creep_states.insert(creep_name, creep_state);
-                }
-                None => {
-                    creep_states.insert(creep_name, CreepState { target: None });
-                }
-            }
+            let creep_state = match creep_states.remove(&creep_name) {
+                Some(creep_state) => run_creep(&creep, creep_state),
+                None => CreepState {
+                    role: role_from_creep_name(&creep_name),
+                    state: State::Collecting,
+                    target: None,
+                },
+            };
+
+            save_creep_state_to_memory(&creep_name, &creep_state);
+            creep_states.insert(creep_name, creep_state);
         }
     });
 }
@@ -72,28 +282,53 @@ fn run_creep(creep: &Creep, creep_state: CreepState) -> CreepState {
         return creep_state;
     }
 
-    return match creep_state.target {
-        Some(creep_target) => {
-            let keep_target = run_creep_by_target(creep, &creep_target);
+    let state = next_state(creep, creep_state.state);
 
-            CreepState {
-                target: if keep_target {
-                    creep_state.target
-                } else {
-                    find_target(creep)
-                },
+    // a phase change always re-picks a target; within the same phase we only
+    // re-pick once the locked target stops being actionable (consumed,
+    // depleted, or destroyed), never just because the action failed for a
+    // store-threshold reason, since that's exactly what `next_state` already
+    // gates on
+    let target = if state == creep_state.state {
+        match creep_state.target {
+            Some(creep_target) if run_creep_by_target(creep, &creep_target) => {
+                Some(creep_target)
             }
+            _ => find_target(creep, creep_state.role, state),
         }
-        None => CreepState {
-            target: find_target(creep),
-        },
+    } else {
+        find_target(creep, creep_state.role, state)
     };
+
+    CreepState {
+        role: creep_state.role,
+        state,
+        target,
+    }
+}
+
+// flips Collecting -> Working once the store is full, and Working ->
+// Collecting once it's completely empty; any level in between keeps the
+// creep in its current phase
+fn next_state(creep: &Creep, state: State) -> State {
+    let capacity = creep.store().get_capacity(Some(ResourceType::Energy));
+    let used = creep.store().get_used_capacity(Some(ResourceType::Energy));
+
+    match state {
+        State::Collecting if capacity > 0 && used >= capacity => State::Working,
+        State::Working if used == 0 => State::Collecting,
+        _ => state,
+    }
 }
 
 fn run_creep_by_target(creep: &Creep, creep_target: &CreepTarget) -> bool {
     return match &creep_target {
         CreepTarget::Upgrade(controller_id) => run_creep_upgrade(creep, controller_id),
         CreepTarget::Harvest(source_id) => run_creep_harvest(creep, source_id),
+        CreepTarget::Build(site_id) => run_creep_build(creep, site_id),
+        CreepTarget::Transfer(transfer_target) => run_creep_transfer(creep, transfer_target),
+        CreepTarget::Withdraw(withdraw_target) => run_creep_withdraw(creep, withdraw_target),
+        CreepTarget::Pickup(resource_id) => run_creep_pickup(creep, resource_id),
     };
 }
 
@@ -133,24 +368,273 @@ fn run_creep_harvest(creep: &Creep, source_id: &ObjectId<Source>) -> bool {
     };
 }
 
-fn find_target(creep: &Creep) -> Option<CreepTarget> {
-    let room = creep.room().expect("couldn't resolve creep room");
+fn run_creep_build(creep: &Creep, site_id: &ObjectId<ConstructionSite>) -> bool {
+    if creep.store().get_used_capacity(Some(ResourceType::Energy)) <= 0 {
+        return false;
+    }
 
-    if creep.store().get_used_capacity(Some(ResourceType::Energy)) > 0 {
-        for structure in room.find(find::STRUCTURES).iter() {
-            // find a structure and upgrade it
-            if let StructureObject::StructureController(controller) = structure {
-                return Some(CreepTarget::Upgrade(controller.id()));
+    return match site_id.resolve() {
+        Some(site) => match creep.build(&site) {
+            ReturnCode::Ok => true,
+            ReturnCode::NotInRange => {
+                creep.move_to(&site);
+                true
             }
+            _ => false,
+        },
+        None => false,
+    };
+}
+
+fn run_creep_transfer(creep: &Creep, transfer_target: &TransferTarget) -> bool {
+    if creep.store().get_used_capacity(Some(ResourceType::Energy)) <= 0 {
+        return false;
+    }
+
+    return match transfer_target {
+        TransferTarget::Spawn(spawn_id) => match spawn_id.resolve() {
+            Some(spawn) => run_creep_transfer_to(creep, &spawn),
+            None => false,
+        },
+        TransferTarget::Extension(extension_id) => match extension_id.resolve() {
+            Some(extension) => run_creep_transfer_to(creep, &extension),
+            None => false,
+        },
+        TransferTarget::Storage(storage_id) => match storage_id.resolve() {
+            Some(storage) => run_creep_transfer_to(creep, &storage),
+            None => false,
+        },
+    };
+}
+
+fn run_creep_transfer_to<T: Transferable + RoomObjectProperties>(
+    creep: &Creep,
+    target: &T,
+) -> bool {
+    return match creep.transfer(target, ResourceType::Energy, None) {
+        ReturnCode::Ok => true,
+        ReturnCode::NotInRange => {
+            creep.move_to(target);
+            true
         }
+        _ => false,
+    };
+}
+
+fn run_creep_withdraw(creep: &Creep, withdraw_target: &WithdrawTarget) -> bool {
+    if creep.store().get_free_capacity(Some(ResourceType::Energy)) <= 0 {
+        return false;
     }
 
+    return match withdraw_target {
+        WithdrawTarget::Container(container_id) => match container_id.resolve() {
+            Some(container) => run_creep_withdraw_from(creep, &container),
+            None => false,
+        },
+        WithdrawTarget::Storage(storage_id) => match storage_id.resolve() {
+            Some(storage) => run_creep_withdraw_from(creep, &storage),
+            None => false,
+        },
+    };
+}
+
+fn run_creep_withdraw_from<T: Withdrawable + RoomObjectProperties>(
+    creep: &Creep,
+    target: &T,
+) -> bool {
+    return match creep.withdraw(target, ResourceType::Energy, None) {
+        ReturnCode::Ok => true,
+        ReturnCode::NotInRange => {
+            creep.move_to(target);
+            true
+        }
+        _ => false,
+    };
+}
+
+fn run_creep_pickup(creep: &Creep, resource_id: &ObjectId<Resource>) -> bool {
+    if creep.store().get_free_capacity(Some(ResourceType::Energy)) <= 0 {
+        return false;
+    }
+
+    return match resource_id.resolve() {
+        Some(resource) => match creep.pickup(&resource) {
+            ReturnCode::Ok => true,
+            ReturnCode::NotInRange => {
+                creep.move_to(&resource);
+                true
+            }
+            _ => false,
+        },
+        None => false,
+    };
+}
+
+// dispatches on phase first, then on the role-appropriate target within that
+// phase, per the explicit FSM
+fn find_target(creep: &Creep, role: Role, state: State) -> Option<CreepTarget> {
+    return match state {
+        State::Collecting => find_collect_target(creep, role),
+        State::Working => find_work_target(creep, role),
+    };
+}
+
+// haulers have no WORK part, so they can't harvest a source themselves -
+// they draw energy back out of whatever the harvesters dropped or stored;
+// every other role collects by harvesting directly
+fn find_collect_target(creep: &Creep, role: Role) -> Option<CreepTarget> {
+    return match role {
+        Role::Hauler => find_collect_target_hauler(creep),
+        _ => find_collect_target_harvest(creep),
+    };
+}
+
+fn find_collect_target_harvest(creep: &Creep) -> Option<CreepTarget> {
     return match creep.pos().find_closest_by_path(find::SOURCES_ACTIVE, None) {
         Some(source) => Some(CreepTarget::Harvest(source.id())),
         None => None,
     };
 }
 
+fn find_collect_target_hauler(creep: &Creep) -> Option<CreepTarget> {
+    let room = creep.room().expect("couldn't resolve creep room");
+
+    if let Some(resource) = room
+        .find(find::DROPPED_RESOURCES)
+        .into_iter()
+        .find(|resource| resource.resource_type() == ResourceType::Energy)
+    {
+        return Some(CreepTarget::Pickup(resource.id()));
+    }
+
+    for structure in room.find(find::STRUCTURES).iter() {
+        match structure {
+            StructureObject::StructureContainer(container) => {
+                if container.store().get_used_capacity(Some(ResourceType::Energy)) > 0 {
+                    return Some(CreepTarget::Withdraw(WithdrawTarget::Container(
+                        container.id(),
+                    )));
+                }
+            }
+            StructureObject::StructureStorage(storage) => {
+                if storage.store().get_used_capacity(Some(ResourceType::Energy)) > 0 {
+                    return Some(CreepTarget::Withdraw(WithdrawTarget::Storage(storage.id())));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn find_work_target(creep: &Creep, role: Role) -> Option<CreepTarget> {
+    return match role {
+        // harvesters have a WORK part and sit on the source all day, so
+        // whatever they're carrying when they tip into Working should go
+        // into the spawn economy first - that's the only way spawns and
+        // extensions ever get refilled; upgrading only once nothing needs it
+        Role::Harvester => find_work_target_fill(creep).or_else(|| find_work_target_upgrade(creep)),
+        Role::Upgrader => find_work_target_upgrade(creep),
+        Role::Builder => find_work_target_build(creep),
+        Role::Hauler => find_work_target_transfer(creep),
+    };
+}
+
+fn find_work_target_upgrade(creep: &Creep) -> Option<CreepTarget> {
+    let room = creep.room().expect("couldn't resolve creep room");
+
+    for structure in room.find(find::STRUCTURES).iter() {
+        // find a structure and upgrade it
+        if let StructureObject::StructureController(controller) = structure {
+            return Some(CreepTarget::Upgrade(controller.id()));
+        }
+    }
+
+    return None;
+}
+
+fn find_work_target_build(creep: &Creep) -> Option<CreepTarget> {
+    let room = creep.room().expect("couldn't resolve creep room");
+
+    match room.find(find::CONSTRUCTION_SITES).into_iter().next() {
+        Some(site) => Some(CreepTarget::Build(site.id())),
+        // nothing to build right now; upgrading is never wasted
+        None => find_work_target_upgrade(creep),
+    }
+}
+
+// any spawn or extension with room left in its tank; shared by every role
+// that's able to refill the spawn economy, so it carries no fallback of its
+// own - callers decide what to do once nothing needs topping up
+fn find_work_target_fill(creep: &Creep) -> Option<CreepTarget> {
+    let room = creep.room().expect("couldn't resolve creep room");
+
+    for structure in room.find(find::STRUCTURES).iter() {
+        match structure {
+            StructureObject::StructureSpawn(spawn) => {
+                if spawn.store().get_free_capacity(Some(ResourceType::Energy)) > 0 {
+                    return Some(CreepTarget::Transfer(TransferTarget::Spawn(spawn.id())));
+                }
+            }
+            StructureObject::StructureExtension(extension) => {
+                if extension.store().get_free_capacity(Some(ResourceType::Energy)) > 0 {
+                    return Some(CreepTarget::Transfer(TransferTarget::Extension(
+                        extension.id(),
+                    )));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// haulers have no WORK part, so unlike every other role they can't fall back
+// to upgrading once spawns/extensions are full - `upgrade_controller` would
+// just fail with ERR_NO_BODYPART. Surplus energy goes into storage instead so
+// it isn't stranded on the hauler; if there's no storage to take it, the
+// hauler holds rather than being handed a target it can't act on
+fn find_work_target_transfer(creep: &Creep) -> Option<CreepTarget> {
+    if let Some(target) = find_work_target_fill(creep) {
+        return Some(target);
+    }
+
+    let room = creep.room().expect("couldn't resolve creep room");
+    match room.storage() {
+        Some(storage) if storage.store().get_free_capacity(Some(ResourceType::Energy)) > 0 => {
+            Some(CreepTarget::Transfer(TransferTarget::Storage(storage.id())))
+        }
+        _ => None,
+    }
+}
+
+fn count_creeps_by_role(creep_states: &HashMap<String, CreepState>, role: Role) -> u32 {
+    creep_states
+        .values()
+        .filter(|creep_state| creep_state.role == role)
+        .count() as u32
+}
+
+// repeats `segment` as many times as the room's energy capacity can afford,
+// capped at screeps' 50-part body limit, so the same role config yields a
+// small body early on and a much larger one once the room's extensions are
+// built out. Also capped by `energy_available` - the energy actually sitting
+// in the room right now - so a colony that's been wiped out after building up
+// capacity still scales the body down to something it can afford today
+// instead of holding out for a room full of energy that nothing is left
+// alive to bring in
+fn body_for_energy(segment: &[Part], energy_available: u32, energy_capacity: u32) -> Vec<Part> {
+    let segment_cost: u32 = segment.iter().map(|part| part.cost()).sum();
+    let max_by_capacity = energy_capacity / segment_cost;
+    let max_by_available = energy_available / segment_cost;
+    let max_by_parts = MAX_BODY_PARTS / segment.len() as u32;
+    let reps = max_by_capacity.min(max_by_available).min(max_by_parts).max(1);
+
+    segment.iter().copied().cycle().take((reps * segment.len() as u32) as usize).collect()
+}
+
 fn spawn_creeps() {
     // Game::spawns returns a `js_sys::Object`, which is a light reference to an
     // object of any kind which is held on the javascript heap.
@@ -160,18 +644,39 @@ fn spawn_creeps() {
     //
     // They are returned as wasm_bindgen::JsValue references, which we can safely
     // assume are StructureSpawn objects as returned from js without checking first
+    let mut counts_by_role = CREEP_STATES.with(|creep_states_refcell| {
+        let creep_states = creep_states_refcell.borrow();
+        ROLES
+            .into_iter()
+            .map(|role| (role, count_creeps_by_role(&creep_states, role)))
+            .collect::<HashMap<Role, u32>>()
+    });
+
     let mut additional = 0;
     for spawn in game::spawns().values() {
         debug!("running spawn {}", String::from(spawn.name()));
 
-        let body = [Part::Move, Part::Move, Part::Carry, Part::Work];
-        if spawn.room().unwrap().energy_available() >= body.iter().map(|p| p.cost()).sum() {
+        let config = ROLE_CONFIGS
+            .iter()
+            .find(|config| counts_by_role[&config.role] < config.count);
+
+        let config = match config {
+            Some(config) => config,
+            None => continue,
+        };
+
+        let room = spawn.room().expect("couldn't resolve spawn room");
+        let body = body_for_energy(
+            config.body_segment,
+            room.energy_available(),
+            room.energy_capacity_available(),
+        );
+        let body_cost: u32 = body.iter().map(|part| part.cost()).sum();
+
+        if room.energy_available() >= body_cost {
             // create a unique name, spawn.
             let name_base = game::time();
-            let name = format!("{}-{}", name_base, additional);
-            // note that this bot has a fatal flaw; spawning a creep
-            // creates Memory.creeps[creep_name] which will build up forever;
-            // these memory entries should be prevented (todo doc link on how) or cleaned up
+            let name = format!("{}-{}-{}", config.role.name(), name_base, additional);
             let res = spawn.spawn_creep(&body, &name);
 
             // todo once fixed in branch this should be ReturnCode::Ok instead of this i8 grumble grumble
@@ -179,7 +684,53 @@ fn spawn_creeps() {
                 warn!("couldn't spawn: {:?}", res);
             } else {
                 additional += 1;
+                *counts_by_role.get_mut(&config.role).unwrap() += 1;
+            }
+        }
+    }
+}
+
+// guard thresholds for `run_power_spawns`, mirroring the conservative
+// checks mature colony bots use before burning power: don't touch it until
+// the room has energy to spare, and don't bother the power spawn unless it
+// actually holds enough of both resources to make processing worthwhile
+const POWER_SPAWN_MIN_ROOM_ENERGY: u32 = 300_000;
+const POWER_SPAWN_MIN_ENERGY: u32 = 50;
+const POWER_SPAWN_MIN_POWER: u32 = 0;
+
+fn run_power_spawns() {
+    for room in game::rooms().values() {
+        let stored_energy = room
+            .storage()
+            .map(|storage| storage.store().get_used_capacity(Some(ResourceType::Energy)))
+            .unwrap_or(0);
+
+        if stored_energy < POWER_SPAWN_MIN_ROOM_ENERGY {
+            continue;
+        }
+
+        for structure in room.find(find::MY_STRUCTURES).iter() {
+            if let StructureObject::StructurePowerSpawn(power_spawn) = structure {
+                run_power_spawn(power_spawn);
             }
         }
     }
 }
+
+fn run_power_spawn(power_spawn: &StructurePowerSpawn) {
+    let energy = power_spawn
+        .store()
+        .get_used_capacity(Some(ResourceType::Energy));
+    let power = power_spawn
+        .store()
+        .get_used_capacity(Some(ResourceType::Power));
+
+    if energy <= POWER_SPAWN_MIN_ENERGY || power <= POWER_SPAWN_MIN_POWER {
+        return;
+    }
+
+    let res = power_spawn.process_power();
+    if res != ReturnCode::Ok {
+        warn!("couldn't process power: {:?}", res);
+    }
+}
@@ -0,0 +1,271 @@
+use std::str::FromStr;
+
+use log::*;
+use screeps::{
+    find, game, memory, prelude::*, ObjectId, PowerCreep, PowerType, ResourceType, ReturnCode,
+    Room, RoomName, Source, StructureExtension, StructureObject, StructurePowerSpawn,
+    StructureSpawn,
+};
+
+// renew an operator once its remaining lifetime drops below this many ticks,
+// rather than waiting until it's about to expire mid-task
+const RENEW_TTL_THRESHOLD: u32 = 1_000;
+
+// key under each operator's own `Memory.powerCreeps[name]` dict where the
+// name of its assigned room is pinned the first time it's seen
+const ASSIGNED_ROOM_MEMORY_KEY: &str = "room";
+
+// the powers an operator can apply: PWR_GENERATE_OPS, which needs no target,
+// plus the ops-economy powers; each targeted variant carries the id of the
+// structure/source it targets so `apply` can resolve a fresh reference and
+// act without the scheduler in `run_power_creep` needing to know the target
+// type. Adding a new power is just a new variant plus the four match arms
+// below.
+enum Power {
+    GenerateOps,
+    OperateSpawn(ObjectId<StructureSpawn>),
+    OperateExtension(ObjectId<StructureExtension>),
+    RegenSource(ObjectId<Source>),
+}
+
+impl Power {
+    fn power_type(&self) -> PowerType {
+        match self {
+            Power::GenerateOps => PowerType::GenerateOps,
+            Power::OperateSpawn(_) => PowerType::OperateSpawn,
+            Power::OperateExtension(_) => PowerType::OperateExtension,
+            Power::RegenSource(_) => PowerType::RegenSource,
+        }
+    }
+
+    // ops cost to invoke the power once, per the power constants
+    fn cost(&self) -> u32 {
+        match self {
+            Power::GenerateOps => 0,
+            Power::OperateSpawn(_) => 100,
+            Power::OperateExtension(_) => 2,
+            Power::RegenSource(_) => 0,
+        }
+    }
+
+    // base cooldown, in ticks, a fresh activation puts the power on; used only
+    // for logging here, `power_ready` reads the live remaining cooldown off
+    // `creep.powers()` since that reflects prior activations this colony has made
+    fn cooldown(&self) -> u32 {
+        match self {
+            Power::GenerateOps => 50,
+            Power::OperateSpawn(_) => 300,
+            Power::OperateExtension(_) => 50,
+            Power::RegenSource(_) => 100,
+        }
+    }
+
+    fn apply(&self, creep: &PowerCreep) -> ReturnCode {
+        match self {
+            Power::GenerateOps => creep.use_power(PowerType::GenerateOps, None),
+            Power::OperateSpawn(id) => match id.resolve() {
+                Some(spawn) => creep.use_power(PowerType::OperateSpawn, Some(&spawn)),
+                None => ReturnCode::NotFound,
+            },
+            Power::OperateExtension(id) => match id.resolve() {
+                Some(extension) => creep.use_power(PowerType::OperateExtension, Some(&extension)),
+                None => ReturnCode::NotFound,
+            },
+            Power::RegenSource(id) => match id.resolve() {
+                Some(source) => creep.use_power(PowerType::RegenSource, Some(&source)),
+                None => ReturnCode::NotFound,
+            },
+        }
+    }
+}
+
+pub fn run_power_creeps() {
+    for power_creep in game::power_creeps().values() {
+        run_power_creep(&power_creep);
+    }
+}
+
+fn run_power_creep(creep: &PowerCreep) {
+    let power_spawn = match find_power_spawn() {
+        Some(power_spawn) => power_spawn,
+        None => return,
+    };
+
+    if creep.ticks_to_live().is_none() {
+        spawn_operator(creep, &power_spawn);
+        return;
+    }
+
+    if creep
+        .ticks_to_live()
+        .map(|ttl| ttl < RENEW_TTL_THRESHOLD)
+        .unwrap_or(false)
+    {
+        renew_operator(creep, &power_spawn);
+    }
+
+    let room = match assigned_room(creep, &power_spawn) {
+        Some(room) => room,
+        None => return,
+    };
+
+    if !move_to_assigned_room(creep, &room) {
+        return;
+    }
+
+    for power in applicable_powers(&room) {
+        run_power(creep, &power);
+    }
+}
+
+// an operator's assigned room is pinned into its own Memory the first time
+// it's seen, defaulting to the power spawn's room, so it keeps servicing the
+// same room even if other owned rooms gain power spawns later
+fn assigned_room(creep: &PowerCreep, power_spawn: &StructurePowerSpawn) -> Option<Room> {
+    let power_creeps_memory = match memory::ROOT.dict_or_create("powerCreeps") {
+        Ok(dict) => dict,
+        Err(e) => {
+            warn!("couldn't get powerCreeps dictionary from memory: {:?}", e);
+            return power_spawn.room();
+        }
+    };
+
+    let creep_memory = match power_creeps_memory.dict_or_create(&creep.name()) {
+        Ok(dict) => dict,
+        Err(e) => {
+            warn!("couldn't get memory for power creep {}: {:?}", creep.name(), e);
+            return power_spawn.room();
+        }
+    };
+
+    if let Ok(Some(room_name)) = creep_memory.get::<String>(ASSIGNED_ROOM_MEMORY_KEY) {
+        if let Ok(room_name) = RoomName::from_str(&room_name) {
+            if let Some(room) = game::rooms().get(room_name) {
+                return Some(room);
+            }
+        }
+    }
+
+    let room = power_spawn.room()?;
+    creep_memory.set(ASSIGNED_ROOM_MEMORY_KEY, room.name().to_string());
+    Some(room)
+}
+
+fn find_power_spawn() -> Option<StructurePowerSpawn> {
+    game::rooms().values().find_map(|room| {
+        room.find(find::MY_STRUCTURES)
+            .into_iter()
+            .find_map(|structure| match structure {
+                StructureObject::StructurePowerSpawn(power_spawn) => Some(power_spawn),
+                _ => None,
+            })
+    })
+}
+
+fn spawn_operator(creep: &PowerCreep, power_spawn: &StructurePowerSpawn) {
+    let res = creep.spawn(power_spawn);
+    if res != ReturnCode::Ok {
+        warn!("couldn't spawn power creep {}: {:?}", creep.name(), res);
+    }
+}
+
+fn renew_operator(creep: &PowerCreep, power_spawn: &StructurePowerSpawn) {
+    match creep.renew(power_spawn) {
+        ReturnCode::Ok => {}
+        ReturnCode::NotInRange => creep.move_to(power_spawn),
+        res => warn!("couldn't renew power creep {}: {:?}", creep.name(), res),
+    }
+}
+
+// walks the operator into its assigned room if it isn't there yet; returns
+// whether it's safe to apply powers this tick
+fn move_to_assigned_room(creep: &PowerCreep, room: &Room) -> bool {
+    if creep.room().map(|creep_room| creep_room.name()) == Some(room.name()) {
+        return true;
+    }
+
+    if let Some(controller) = room.controller() {
+        creep.move_to(&controller);
+    }
+
+    false
+}
+
+// every power worth considering this tick, GenerateOps first so the operator
+// always tops up its ops reserve before spending any of it. `creep.powers()`
+// cooldowns are a start-of-tick snapshot, so at most one candidate per power
+// type is picked here - queuing two OperateExtension targets, say, would have
+// the second `use_power` fail with ERR_TIRED since the first hasn't ticked
+// the snapshot down yet
+fn applicable_powers(room: &Room) -> Vec<Power> {
+    let mut powers = vec![Power::GenerateOps];
+
+    let operate_spawn = room
+        .find(find::MY_STRUCTURES)
+        .into_iter()
+        .find_map(|structure| match structure {
+            StructureObject::StructureSpawn(spawn) => Some(Power::OperateSpawn(spawn.id())),
+            _ => None,
+        });
+    powers.extend(operate_spawn);
+
+    let operate_extension =
+        room.find(find::MY_STRUCTURES)
+            .into_iter()
+            .find_map(|structure| match structure {
+                StructureObject::StructureExtension(extension) => {
+                    Some(Power::OperateExtension(extension.id()))
+                }
+                _ => None,
+            });
+    powers.extend(operate_extension);
+
+    let regen_source = room
+        .find(find::SOURCES)
+        .into_iter()
+        .next()
+        .map(|source| Power::RegenSource(source.id()));
+    powers.extend(regen_source);
+
+    powers
+}
+
+fn run_power(creep: &PowerCreep, power: &Power) {
+    if !power_ready(creep, power) {
+        return;
+    }
+
+    let res = power.apply(creep);
+    if res == ReturnCode::Ok {
+        debug!(
+            "applied power {:?} on {} (cooldown {} ticks)",
+            power.power_type(),
+            creep.name(),
+            power.cooldown()
+        );
+    } else {
+        debug!(
+            "power {:?} on {} returned {:?}",
+            power.power_type(),
+            creep.name(),
+            res
+        );
+    }
+}
+
+fn power_ready(creep: &PowerCreep, power: &Power) -> bool {
+    let off_cooldown = creep
+        .powers()
+        .get(power.power_type())
+        .map(|info| info.cooldown() == 0)
+        .unwrap_or(false);
+
+    if !off_cooldown {
+        return false;
+    }
+
+    match power {
+        Power::GenerateOps => creep.store().get_free_capacity(Some(ResourceType::Ops)) > 0,
+        _ => creep.store().get_used_capacity(Some(ResourceType::Ops)) >= power.cost(),
+    }
+}